@@ -23,50 +23,91 @@ extern crate super_analyzer;
 extern crate colored;
 #[macro_use]
 extern crate log;
+extern crate schemars;
+#[macro_use]
+extern crate serde_json;
+extern crate regex;
 
 use std::io::{self, Write};
 use std::time::{Instant, Duration};
-use std::thread::sleep;
+use std::thread::{self, sleep};
 use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
 
 use colored::Colorize;
 use log::LogLevel;
+use schemars::JsonSchema;
+use regex::Regex;
 use super_analyzer::*;
 
 
 #[allow(print_stdout)]
 fn main() {
-    if let Err(e) = run() {
-        error!("{}", e);
+    let cli = cli::generate().get_matches();
+    let message_format = cli.value_of("message-format").unwrap_or("human").to_owned();
 
-        for e in e.iter().skip(1) {
-            println!("\t{}{}", "Caused by: ".bold(), e);
-        }
+    if let Err(e) = run(cli, &message_format) {
+        let primary = e.to_string();
+        let caused_by: Vec<String> = e.iter().skip(1).map(|cause| cause.to_string()).collect();
+        let backtrace = e.backtrace().map(|bt| format!("{:?}", bt));
+        let exit_code: i32 = e.into();
 
-        if !log_enabled!(LogLevel::Debug) {
-            println!(
-                "If you need more information, try to run the program again with the {} flag.",
-                "-v".bold()
-            );
+        match message_format.as_str() {
+            "json" => print_error_json(&primary, &caused_by, exit_code, backtrace.as_ref()),
+            _ => print_error_human(&primary, &caused_by, backtrace.as_ref()),
         }
 
-        if let Some(backtrace) = e.backtrace() {
-            #[allow(use_debug)]
-            {
-                println!("backtrace: {:?}", backtrace);
-            }
-        }
+        ::std::process::exit(exit_code);
+    }
+}
+
+/// Prints the error chain the way SUPER always has: the primary error through the logger, each
+/// `Caused by` cause, a hint to re-run with `-v` when not already at debug level, and the
+/// backtrace when one was captured.
+fn print_error_human(primary: &str, caused_by: &[String], backtrace: Option<&String>) {
+    error!("{}", primary);
+
+    for cause in caused_by {
+        println!("\t{}{}", "Caused by: ".bold(), cause);
+    }
+
+    if !log_enabled!(LogLevel::Debug) {
+        println!(
+            "If you need more information, try to run the program again with the {} flag.",
+            "-v".bold()
+        );
+    }
 
-        ::std::process::exit(e.into());
+    if let Some(backtrace) = backtrace {
+        println!("backtrace: {}", backtrace);
     }
 }
 
-fn run() -> Result<()> {
-    let cli = cli::generate().get_matches();
-    let verbose = cli.is_present("verbose");
-    initialize_logger(verbose);
+/// Prints the error chain as a single JSON object, so CI pipelines and wrapper tooling can react
+/// to specific failure stages instead of scraping colored terminal text.
+fn print_error_json(primary: &str, caused_by: &[String], exit_code: i32, backtrace: Option<&String>) {
+    let report = json!({
+        "error": primary,
+        "caused_by": caused_by,
+        "exit_code": exit_code,
+        "backtrace": backtrace,
+    });
+
+    println!("{}", report);
+}
+
+fn run(cli: ArgMatches<'static>, message_format: &str) -> Result<()> {
+    let log_level = effective_log_level(cli.occurrences_of("verbose"), cli.occurrences_of("quiet"));
+    initialize_logger(log_level);
+
+    if let Some(schema_target) = cli.value_of("print-schema") {
+        return print_schema(schema_target);
+    }
 
     let mut config = initialize_config(cli)?;
+    config.set_log_level(log_level);
 
     if !config.check() {
         let mut error_string = String::from("Configuration errors were found:\n");
@@ -85,7 +126,9 @@ fn run() -> Result<()> {
         return Err(ErrorKind::Config(error_string).into());
     }
 
-    if config.is_verbose() {
+    let human = message_format != "json";
+
+    if config.is_verbose() && human {
         for c in BANNER.chars() {
             print!("{}", c);
             io::stdout().flush().unwrap();
@@ -104,15 +147,26 @@ fn run() -> Result<()> {
     }
 
     let mut benchmarks = BTreeMap::new();
+    let output_lock = Arc::new(Mutex::new(()));
 
     let total_start = Instant::now();
-    for package in config.get_app_packages() {
-        config.reset_force();
-        analyze_package(package, &mut config, &mut benchmarks)
-            .chain_err(|| "Application analysis failed")?;
+    let jobs = config.get_jobs();
+    if jobs > 1 {
+        analyze_packages_parallel(&config, jobs, message_format, &output_lock, &mut benchmarks)?;
+    } else {
+        for package in config.get_app_packages() {
+            config.reset_force();
+            analyze_package(
+                package,
+                &mut config,
+                message_format,
+                &output_lock,
+                &mut benchmarks,
+            ).chain_err(|| "Application analysis failed")?;
+        }
     }
 
-    if config.is_bench() {
+    if config.is_bench() && human {
         let total_time = Benchmark::new("Total time", total_start.elapsed());
         println!();
         println!("{}", "Benchmarks:".bold());
@@ -130,6 +184,66 @@ fn run() -> Result<()> {
 }
 
 
+/// Prints the JSON Schema for either `config.toml` (`"config"`) or the generated
+/// `results.json` (`"results"`) and returns before the analysis pipeline starts.
+///
+/// The schemas are derived from the `Config` and `Results` types themselves (via `schemars`'s
+/// `#[derive(JsonSchema)]`) rather than hand-written, so they stay in sync with those types'
+/// *field structure* as it evolves. `schemars` derives purely from the shape of the struct/enum,
+/// not from any custom `Serialize`/`Deserialize` impl, so a type with a hand-written serde impl
+/// needs a matching hand-written `JsonSchema` impl or the emitted schema will describe the
+/// derived (default) representation instead of what actually gets (de)serialized. `Criticality`
+/// is exactly such a case: it serializes as a string via a custom `Serialize` impl, so it carries
+/// its own `impl JsonSchema for Criticality` below, hand-written to match, producing the string
+/// enum `["warning", "low", "medium", "high", "critical"]`.
+fn print_schema(target: &str) -> Result<()> {
+    let schema = match target {
+        "config" => serde_json::to_string_pretty(&schemars::schema_for!(Config)),
+        "results" => serde_json::to_string_pretty(&schemars::schema_for!(Results)),
+        other => {
+            return Err(ErrorKind::Config(format!("unknown schema target: {}", other)).into())
+        }
+    };
+
+    println!(
+        "{}",
+        schema.chain_err(|| "could not serialize the JSON Schema")?
+    );
+
+    Ok(())
+}
+
+/// Emits a per-stage completion event for `package_name` when running with
+/// `--message-format json`, so CI pipelines and wrapper tooling can follow progress through
+/// `decompression`, `dex2jar`, `decompile`, `static_analysis` and `report` without scraping the
+/// human-formatted output.
+fn emit_stage_event(message_format: &str, package_name: &str, stage: &str) {
+    if message_format == "json" {
+        println!(
+            "{}",
+            json!({
+                "event": "stage_complete",
+                "package": package_name,
+                "stage": stage,
+            })
+        );
+    }
+}
+
+/// Computes the effective log level from the counted `-v`/`--verbose` and `-q`/`--quiet`
+/// occurrences, with `Info` as the baseline (e.g. `-qq` → `Error`, `-q` → `Warn`, no flags →
+/// `Info`, `-v` → `Debug`, `-vv` → `Trace`).
+fn effective_log_level(verbose_count: u64, quiet_count: u64) -> LogLevelFilter {
+    let verbosity = verbose_count as i64 - quiet_count as i64;
+    match verbosity {
+        v if v <= -2 => LogLevelFilter::Error,
+        -1 => LogLevelFilter::Warn,
+        0 => LogLevelFilter::Info,
+        1 => LogLevelFilter::Debug,
+        _ => LogLevelFilter::Trace,
+    }
+}
+
 /// Initialize the config with the config files and command line options
 /// On UNIX, if local file ('config.toml') does not exists, but the global one does
 /// ('/etc/super-analyzer/config.toml'), the latter is used.
@@ -160,17 +274,73 @@ fn initialize_config(cli: ArgMatches<'static>) -> Result<Config> {
     Ok(config)
 }
 
+/// Coverage of classes that were successfully decompiled vs. attempted by `jd-cli`.
+#[derive(Debug, Clone, Copy, Default)]
+struct DecompilationCoverage {
+    /// Number of classes `jd-cli` attempted to decompile.
+    attempted: usize,
+    /// Number of classes that were decompiled without error.
+    succeeded: usize,
+}
+
+/// Scans captured `dex2jar`/`jd-cli` stdout and stderr for generic failure or warning markers
+/// (exceptions, errors) and returns one finding message per match, prefixed with `tool_name`.
+fn scan_tool_warnings(output: &ToolOutput, tool_name: &str) -> Vec<String> {
+    let warning_re = Regex::new(r"(?m)^.*(?:Exception|ERROR).*$").unwrap();
+    warning_re
+        .find_iter(&output.stdout)
+        .chain(warning_re.find_iter(&output.stderr))
+        .map(|m| format!("{}: {}", tool_name, m.as_str().trim()))
+        .collect()
+}
+
+/// Scans captured `jd-cli` output for per-class decompilation failures and computes the overall
+/// decompilation coverage, so analysts know which parts of the app were actually auditable
+/// instead of assuming full coverage.
+///
+/// `jd-cli` logs one `Decompiling <class> ...` line per attempted class, normally to stdout, but
+/// a hard failure (e.g. a corrupt class file) can print the same line to stderr instead, so both
+/// streams are scanned with the same pattern used to count attempts, matching how failures are
+/// already scanned across both streams. These patterns are pinned against the fixtures in the
+/// `it_parses_decompile_output_*` tests below, not against a captured real `jd-cli` run; if the
+/// format actually produced by the installed `jd-cli` drifts from those fixtures, `attempted`
+/// comes back `0` and the caller must treat that as "coverage unknown", not "0% coverage".
+fn parse_decompile_output(output: &ToolOutput) -> (DecompilationCoverage, Vec<String>) {
+    let attempted_re = Regex::new(r"(?m)^Decompiling (\S+)\s*\.\.\.$").unwrap();
+    let failure_re =
+        Regex::new(r"(?m)^(?:Could not decompile|Exception while decompiling) (\S+)").unwrap();
+
+    let attempted = attempted_re.find_iter(&output.stdout).count()
+        + attempted_re.find_iter(&output.stderr).count();
+    let failed_classes: Vec<String> = failure_re
+        .captures_iter(&output.stdout)
+        .chain(failure_re.captures_iter(&output.stderr))
+        .map(|caps| caps[1].to_owned())
+        .collect();
+
+    let coverage = DecompilationCoverage {
+        attempted,
+        succeeded: attempted.saturating_sub(failed_classes.len()),
+    };
+
+    (coverage, failed_classes)
+}
+
 /// Analyzes the given package with the given config.
 fn analyze_package<P: AsRef<Path>>(
     package: P,
     config: &mut Config,
+    message_format: &str,
+    output_lock: &Mutex<()>,
     benchmarks: &mut BTreeMap<String, Vec<Benchmark>>,
 ) -> Result<()> {
+    let human = message_format != "json";
     let package_name = get_package_name(&package);
     if config.is_bench() {
         let _ = benchmarks.insert(package_name.clone(), Vec::with_capacity(4));
     }
-    if !config.is_quiet() {
+    if !config.is_quiet() && human {
+        let _guard = output_lock.lock().unwrap();
         println!();
         println!("Starting analysis of {}.", package_name.italic());
     }
@@ -180,6 +350,7 @@ fn analyze_package<P: AsRef<Path>>(
     decompress(config, &package).chain_err(
         || "apk decompression failed",
     )?;
+    emit_stage_event(message_format, &package_name, "decompression");
 
     if config.is_bench() {
         benchmarks.get_mut(&package_name).unwrap().push(
@@ -193,9 +364,10 @@ fn analyze_package<P: AsRef<Path>>(
 
     let dex_jar_time = Instant::now();
     // Converting the .dex to .jar.
-    dex_to_jar(config, &package).chain_err(
+    let dex_to_jar_output = dex_to_jar(config, &package).chain_err(
         || "Conversion from DEX to JAR failed",
     )?;
+    emit_stage_event(message_format, &package_name, "dex2jar");
 
     if config.is_bench() {
         benchmarks.get_mut(&package_name).unwrap().push(
@@ -207,7 +379,8 @@ fn analyze_package<P: AsRef<Path>>(
         );
     }
 
-    if config.is_verbose() {
+    if config.is_verbose() && human {
+        let _guard = output_lock.lock().unwrap();
         println!();
         println!(
             "Now it's time for the actual decompilation of the source code. We'll translate
@@ -218,9 +391,10 @@ fn analyze_package<P: AsRef<Path>>(
     let decompile_start = Instant::now();
 
     // Decompiling the app
-    decompile(config, &package).chain_err(
+    let decompile_output = decompile(config, &package).chain_err(
         || "JAR decompression failed",
     )?;
+    emit_stage_event(message_format, &package_name, "decompile");
 
     if config.is_bench() {
         benchmarks.get_mut(&package_name).unwrap().push(
@@ -233,9 +407,36 @@ fn analyze_package<P: AsRef<Path>>(
     }
 
     let mut results = Results::init(config, &package)?;
+
+    let parse_start = Instant::now();
+    for warning in scan_tool_warnings(&dex_to_jar_output, "dex2jar") {
+        results.add_warning(warning);
+    }
+    for warning in scan_tool_warnings(&decompile_output, "jd-cli") {
+        results.add_warning(warning);
+    }
+    let (coverage, failed_classes) = parse_decompile_output(&decompile_output);
+    for class in failed_classes {
+        results.add_warning(format!(
+            "The class {} could not be decompiled and was not analyzed.",
+            class
+        ));
+    }
+    if coverage.attempted > 0 {
+        results.set_decompilation_coverage(coverage.succeeded, coverage.attempted);
+    }
+
+    if config.is_bench() {
+        benchmarks.get_mut(&package_name).unwrap().push(Benchmark::new(
+            "Tool output parsing",
+            parse_start.elapsed(),
+        ));
+    }
+
     let static_start = Instant::now();
     // Static application analysis
     static_analysis(config, &package_name, &mut results);
+    emit_stage_event(message_format, &package_name, "static_analysis");
 
     if config.is_bench() {
         benchmarks.get_mut(&package_name).unwrap().push(
@@ -249,7 +450,8 @@ fn analyze_package<P: AsRef<Path>>(
 
     // TODO dynamic analysis
 
-    if !config.is_quiet() {
+    if !config.is_quiet() && human {
+        let _guard = output_lock.lock().unwrap();
         println!();
     }
 
@@ -262,12 +464,17 @@ fn analyze_package<P: AsRef<Path>>(
             )
         },
     )?;
+    emit_stage_event(message_format, &package_name, "report");
 
-    if config.is_verbose() {
-        println!("Everything went smoothly, now you can check all the results.");
-        println!();
-        println!("I will now analyze myself for vulnerabilities…");
+    if config.is_verbose() && human {
+        {
+            let _guard = output_lock.lock().unwrap();
+            println!("Everything went smoothly, now you can check all the results.");
+            println!();
+            println!("I will now analyze myself for vulnerabilities…");
+        }
         sleep(Duration::from_millis(1500));
+        let _guard = output_lock.lock().unwrap();
         println!(
             "Nah, just kidding, I've been developed in {}!",
             "Rust".bold().green()
@@ -321,6 +528,101 @@ fn analyze_package<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Analyzes all configured packages concurrently using a fixed-size worker pool.
+///
+/// A shared queue of packages is drained by `jobs` workers, each one owning its own
+/// `reset_force`-ed clone of `config`, so Java sub-tool invocations and per-package results
+/// folders never collide between packages being analyzed at the same time. Results are
+/// collected back through a channel into `benchmarks`, keeping the final benchmark summary
+/// identical to the sequential (`--jobs 1`) path, modulo ordering. Per-package human-readable
+/// output is serialized through `output_lock` so concurrent workers don't interleave mid-line.
+///
+/// Like the sequential (`--jobs 1`) path, analysis stops as soon as a package fails: once any
+/// worker observes an error, every worker stops picking up *new* packages from the queue and the
+/// first error seen is returned. Packages already in flight when the failure happens are left to
+/// finish (there's no way to cooperatively cancel a running `dex2jar`/`jd-cli` subprocess),
+/// including their report generation, but no further packages are started.
+fn analyze_packages_parallel(
+    config: &Config,
+    jobs: usize,
+    message_format: &str,
+    output_lock: &Arc<Mutex<()>>,
+    benchmarks: &mut BTreeMap<String, Vec<Benchmark>>,
+) -> Result<()> {
+    let queue = Arc::new(Mutex::new(config.get_app_packages().to_vec()));
+    let aborted = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
+
+    let workers: Vec<_> = (0..jobs)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let aborted = Arc::clone(&aborted);
+            let output_lock = Arc::clone(output_lock);
+            let tx = tx.clone();
+            let mut worker_config = config.clone();
+            let message_format = message_format.to_owned();
+            thread::spawn(move || loop {
+                if aborted.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let package = {
+                    let mut queue = queue.lock().unwrap();
+                    queue.pop()
+                };
+                let package = match package {
+                    Some(package) => package,
+                    None => break,
+                };
+
+                worker_config.reset_force();
+                let package_name = get_package_name(&package);
+                let mut package_benchmarks = BTreeMap::new();
+                let outcome = analyze_package(
+                    &package,
+                    &mut worker_config,
+                    &message_format,
+                    &output_lock,
+                    &mut package_benchmarks,
+                ).chain_err(|| "Application analysis failed")
+                    .map(|_| {
+                        package_benchmarks
+                            .remove(&package_name)
+                            .unwrap_or_default()
+                    });
+
+                if outcome.is_err() {
+                    aborted.store(true, Ordering::SeqCst);
+                }
+
+                let _ = tx.send((package_name, outcome));
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let mut first_error = None;
+    for (package_name, outcome) in rx {
+        match outcome {
+            Ok(package_benchmarks) => {
+                let _ = benchmarks.insert(package_name, package_benchmarks);
+            }
+            Err(e) => if first_error.is_none() {
+                first_error = Some(e);
+            },
+        }
+    }
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
 /// Vulnerability criticality
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 pub enum Criticality {
@@ -391,6 +693,26 @@ impl FromStr for Criticality {
     }
 }
 
+impl JsonSchema for Criticality {
+    fn schema_name() -> String {
+        "Criticality".to_owned()
+    }
+
+    fn json_schema(_gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::String.into()),
+            enum_values: Some(vec![
+                "warning".into(),
+                "low".into(),
+                "medium".into(),
+                "high".into(),
+                "critical".into(),
+            ]),
+            ..Default::default()
+        }.into()
+    }
+}
+
 /// Copies the contents of `from` to `to`
 ///
 /// If the destination folder doesn't exist is created. Note that the parent folder must exist. If
@@ -412,7 +734,7 @@ pub fn copy_folder<P: AsRef<Path>>(from: P, to: P) -> Result<()> {
     Ok(())
 }
 
-fn initialize_logger(is_verbose: bool) {
+fn initialize_logger(log_level: LogLevelFilter) {
     let format = |record: &LogRecord| match record.level() {
         LogLevel::Warn => {
             format!(
@@ -433,12 +755,6 @@ fn initialize_logger(is_verbose: bool) {
         _ => format!("{}: {}", record.level(), record.args()),
     };
 
-    let log_level = if is_verbose {
-        LogLevelFilter::Debug
-    } else {
-        LogLevelFilter::Info
-    };
-
     let mut builder = LogBuilder::new();
 
     let builder_state = if let Ok(env_log) = env::var("RUST_LOG") {
@@ -459,6 +775,99 @@ fn initialize_logger(is_verbose: bool) {
 mod tests {
     use Criticality;
     use std::str::FromStr;
+    use {effective_log_level, parse_decompile_output, scan_tool_warnings, LogLevelFilter,
+         ToolOutput};
+
+    #[test]
+    fn it_computes_effective_log_level() {
+        assert_eq!(effective_log_level(0, 2), LogLevelFilter::Error);
+        assert_eq!(effective_log_level(0, 3), LogLevelFilter::Error);
+        assert_eq!(effective_log_level(0, 1), LogLevelFilter::Warn);
+        assert_eq!(effective_log_level(0, 0), LogLevelFilter::Info);
+        assert_eq!(effective_log_level(1, 0), LogLevelFilter::Debug);
+        assert_eq!(effective_log_level(2, 0), LogLevelFilter::Trace);
+        assert_eq!(effective_log_level(3, 0), LogLevelFilter::Trace);
+        // `-v` and `-q` cancel each other out around the `Info` baseline.
+        assert_eq!(effective_log_level(1, 1), LogLevelFilter::Info);
+    }
+
+    #[test]
+    fn it_scans_tool_warnings_across_both_streams() {
+        let output = ToolOutput {
+            stdout: "Converting classes1.dex ...\n\
+                     java.lang.Exception: unsupported opcode\n"
+                .to_owned(),
+            stderr: "ERROR: out of memory\n".to_owned(),
+        };
+
+        let warnings = scan_tool_warnings(&output, "dex2jar");
+        assert_eq!(
+            warnings,
+            vec![
+                "dex2jar: java.lang.Exception: unsupported opcode".to_owned(),
+                "dex2jar: ERROR: out of memory".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_scans_tool_warnings_with_no_matches() {
+        let output = ToolOutput {
+            stdout: "Converting classes1.dex ... OK\n".to_owned(),
+            stderr: String::new(),
+        };
+
+        assert!(scan_tool_warnings(&output, "dex2jar").is_empty());
+    }
+
+    #[test]
+    fn it_parses_decompile_output_full_coverage() {
+        let output = ToolOutput {
+            stdout: "Decompiling com/example/Foo.class ...\n\
+                     Decompiling com/example/Bar.class ...\n"
+                .to_owned(),
+            stderr: String::new(),
+        };
+
+        let (coverage, failed) = parse_decompile_output(&output);
+        assert_eq!(coverage.attempted, 2);
+        assert_eq!(coverage.succeeded, 2);
+        assert!(failed.is_empty());
+    }
+
+    #[test]
+    fn it_parses_decompile_output_with_failures_across_both_streams() {
+        let output = ToolOutput {
+            stdout: "Decompiling com/example/Foo.class ...\n\
+                     Decompiling com/example/Bar.class ...\n\
+                     Could not decompile com/example/Bar.class\n"
+                .to_owned(),
+            stderr: "Exception while decompiling com/example/Baz.class\n".to_owned(),
+        };
+
+        let (coverage, failed) = parse_decompile_output(&output);
+        assert_eq!(coverage.attempted, 2);
+        assert_eq!(
+            failed,
+            vec!["com/example/Bar.class".to_owned(), "com/example/Baz.class".to_owned()]
+        );
+        // `Baz` failed without a matching "Decompiling ..." line in this fixture, so the two
+        // matched failures use up both attempts and `succeeded` comes out at 0.
+        assert_eq!(coverage.succeeded, 0);
+    }
+
+    #[test]
+    fn it_parses_decompile_output_with_no_recognized_lines() {
+        let output = ToolOutput {
+            stdout: String::new(),
+            stderr: String::new(),
+        };
+
+        let (coverage, failed) = parse_decompile_output(&output);
+        assert_eq!(coverage.attempted, 0);
+        assert_eq!(coverage.succeeded, 0);
+        assert!(failed.is_empty());
+    }
 
     #[test]
     fn it_criticality() {